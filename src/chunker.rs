@@ -0,0 +1,186 @@
+use processor::HashAlgorithm;
+
+/// Boundaries are never declared before a chunk reaches this size, so a run
+/// of low gear-hash values can't collapse a file into tiny chunks.
+const MIN_CHUNK_SIZE: usize = 2 * 1024;
+
+/// A boundary is forced here even if the gear hash never hits the mask, so
+/// a run of high gear-hash values can't grow a chunk without bound.
+const MAX_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Low bits of the gear hash that must all be zero to cut a chunk. Chosen
+/// so the expected chunk size is `2.pow(MASK_BITS)` bytes, i.e. ~8 KiB.
+const MASK_BITS: u32 = 13;
+const BOUNDARY_MASK: u64 = (1 << MASK_BITS) - 1;
+
+/// One content-defined chunk of a file, as emitted by [`Chunker`].
+#[derive(Debug, Clone)]
+pub struct ChunkInfo {
+    pub offset: u64,
+    pub length: u64,
+    pub digest: String,
+}
+
+/// Splits a byte stream into content-defined chunks using a rolling "gear"
+/// hash: for every incoming byte the accumulator is shifted left one bit and
+/// a value from a fixed 256-entry table (indexed by the byte) is added in.
+/// A chunk boundary falls wherever the low `MASK_BITS` bits of the
+/// accumulator are all zero, which gives chunk boundaries that depend only
+/// on local content and therefore survive insertions/deletions elsewhere in
+/// the file — the same trick content-addressed backup stores use to dedup
+/// across whole-file hashing's blind spots.
+pub struct Chunker {
+    table: [u64; 256],
+    hash: u64,
+    offset: u64,
+    chunk_start: u64,
+    buffer: Vec<u8>,
+    algorithm: HashAlgorithm,
+    chunks: Vec<ChunkInfo>,
+}
+
+impl Chunker {
+    pub fn new(algorithm: HashAlgorithm) -> Self {
+        Self {
+            table: gear_table(),
+            hash: 0,
+            offset: 0,
+            chunk_start: 0,
+            buffer: Vec::new(),
+            algorithm,
+            chunks: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, bytes: &[u8]) {
+        let mut start = 0;
+
+        while start < bytes.len() {
+            let mut boundary = None;
+
+            for (i, &byte) in bytes[start..].iter().enumerate() {
+                self.offset += 1;
+                self.hash = (self.hash << 1).wrapping_add(self.table[byte as usize]);
+
+                let len = self.buffer.len() + i + 1;
+                let at_boundary = len >= MAX_CHUNK_SIZE
+                    || (len >= MIN_CHUNK_SIZE && self.hash & BOUNDARY_MASK == 0);
+
+                if at_boundary {
+                    boundary = Some(i);
+                    break;
+                }
+            }
+
+            match boundary {
+                Some(i) => {
+                    self.buffer.extend_from_slice(&bytes[start..=start + i]);
+                    self.cut();
+                    start += i + 1;
+                }
+                None => {
+                    self.buffer.extend_from_slice(&bytes[start..]);
+                    start = bytes.len();
+                }
+            }
+        }
+    }
+
+    /// Flushes the trailing partial chunk (if any) and returns every chunk
+    /// found in the stream, in file order.
+    pub fn finish(mut self) -> Vec<ChunkInfo> {
+        if !self.buffer.is_empty() {
+            self.cut();
+        }
+
+        self.chunks
+    }
+
+    fn cut(&mut self) {
+        let mut hasher = self.algorithm.hasher();
+        hasher.update(&self.buffer);
+
+        self.chunks.push(ChunkInfo {
+            offset: self.chunk_start,
+            length: self.buffer.len() as u64,
+            digest: hasher.finish(),
+        });
+
+        self.chunk_start = self.offset;
+        self.buffer.clear();
+        self.hash = 0;
+    }
+}
+
+/// Generates the fixed gear table from a constant seed via splitmix64, so
+/// boundaries are reproducible across runs without shipping a `rand`
+/// dependency for 256 numbers.
+fn gear_table() -> [u64; 256] {
+    let mut table = [0u64; 256];
+    let mut seed = 0x9E37_79B9_7F4A_7C15u64;
+
+    for entry in table.iter_mut() {
+        seed = splitmix64(seed);
+        *entry = seed;
+    }
+
+    table
+}
+
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn never_cuts_below_min_chunk_size() {
+        let mut chunker = Chunker::new(HashAlgorithm::Sha1);
+        chunker.push(&vec![0u8; MIN_CHUNK_SIZE - 1]);
+
+        let chunks = chunker.finish();
+
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].length, (MIN_CHUNK_SIZE - 1) as u64);
+    }
+
+    #[test]
+    fn forces_a_boundary_at_max_chunk_size() {
+        let mut chunker = Chunker::new(HashAlgorithm::Sha1);
+        chunker.push(&vec![0u8; MAX_CHUNK_SIZE + MIN_CHUNK_SIZE]);
+
+        let chunks = chunker.finish();
+
+        assert!(chunks[0].length <= MAX_CHUNK_SIZE as u64);
+        assert!(chunks.iter().all(|chunk| chunk.length <= MAX_CHUNK_SIZE as u64));
+    }
+
+    #[test]
+    fn chunk_boundaries_do_not_depend_on_push_call_granularity() {
+        let data = vec![0u8; MAX_CHUNK_SIZE * 2];
+
+        let mut whole = Chunker::new(HashAlgorithm::Sha1);
+        whole.push(&data);
+        let whole_chunks: Vec<u64> = whole.finish().into_iter().map(|c| c.length).collect();
+
+        let mut split = Chunker::new(HashAlgorithm::Sha1);
+        for byte_chunk in data.chunks(17) {
+            split.push(byte_chunk);
+        }
+        let split_chunks: Vec<u64> = split.finish().into_iter().map(|c| c.length).collect();
+
+        assert_eq!(whole_chunks, split_chunks);
+    }
+
+    #[test]
+    fn empty_input_produces_no_chunks() {
+        let chunker = Chunker::new(HashAlgorithm::Sha1);
+
+        assert!(chunker.finish().is_empty());
+    }
+}
@@ -1,11 +1,102 @@
-use clap::{App, Arg};
+use clap::{App, Arg, SubCommand};
+use processor::HashAlgorithm;
+use query::FuzzyOptions;
 use std::path::PathBuf;
 
+use schema::{DEFAULT_INDEX_DIR, DEFAULT_LANG};
+
+const INDEX_DIR: &str = "index-dir";
 const OUTPUT: &str = "output";
+const DIRECTORY: &str = "directory";
+const QUERY: &str = "query";
+const PORT: &str = "port";
+const HASH: &str = "hash";
+const DUPLICATES: &str = "duplicates";
+const CHUNKS: &str = "chunks";
+const FUZZY: &str = "fuzzy";
+const FUZZY_TRANSPOSITIONS: &str = "fuzzy-transpositions";
+const LANG: &str = "lang";
+
+const DEFAULT_PORT: &str = "3000";
 
 #[derive(Debug)]
-pub struct CommandArg {
+pub struct IndexArgs {
+    pub directory: PathBuf,
     pub output: Option<PathBuf>,
+    pub index_dir: PathBuf,
+    pub algorithm: HashAlgorithm,
+    pub duplicates: bool,
+    pub chunks: bool,
+    pub lang: String,
+}
+
+#[derive(Debug)]
+pub struct SearchArgs {
+    pub query: String,
+    pub index_dir: PathBuf,
+    pub fuzzy: Option<FuzzyOptions>,
+    pub lang: String,
+}
+
+#[derive(Debug)]
+pub struct ServeArgs {
+    pub port: u16,
+    pub index_dir: PathBuf,
+    pub fuzzy: Option<FuzzyOptions>,
+    pub lang: String,
+}
+
+#[derive(Debug)]
+pub enum Command {
+    Index(IndexArgs),
+    Search(SearchArgs),
+    Serve(ServeArgs),
+}
+
+fn index_dir_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(INDEX_DIR)
+        .help("Path to the tantivy index")
+        .long(INDEX_DIR)
+        .takes_value(true)
+        .value_name("INDEX_DIR")
+        .default_value(DEFAULT_INDEX_DIR)
+}
+
+fn lang_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(LANG)
+        .help("Language for the analyzer's stemmer and case/accent folding")
+        .long(LANG)
+        .alias("tokenizer")
+        .takes_value(true)
+        .value_name("LANG")
+        .possible_values(&["en", "fr", "de", "es", "it", "pt", "nl", "ru"])
+        .default_value(DEFAULT_LANG)
+}
+
+fn fuzzy_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name(FUZZY)
+            .help("Max edit distance for typo-tolerant matching (also settable per-term with a `~N` query suffix)")
+            .long(FUZZY)
+            .takes_value(true)
+            .value_name("DISTANCE")
+            .possible_values(&["1", "2"]),
+        Arg::with_name(FUZZY_TRANSPOSITIONS)
+            .help("Count a transposed pair of adjacent characters as one edit instead of two")
+            .long(FUZZY_TRANSPOSITIONS),
+    ]
+}
+
+fn parse_fuzzy(matches: &clap::ArgMatches) -> Option<FuzzyOptions> {
+    let transpositions = matches.is_present(FUZZY_TRANSPOSITIONS);
+
+    matches
+        .value_of(FUZZY)
+        .and_then(|x| x.parse().ok())
+        .map(|distance| FuzzyOptions {
+            distance,
+            transpositions,
+        })
 }
 
 fn build_cli<'a, 'b>() -> App<'a, 'b> {
@@ -13,13 +104,77 @@ fn build_cli<'a, 'b>() -> App<'a, 'b> {
         .version(crate_version!())
         .author(crate_authors!())
         .about(crate_description!())
-        .arg(
-            Arg::with_name(OUTPUT)
-                .help("Output file path")
-                .short("o")
-                .long(OUTPUT)
-                .takes_value(true)
-                .value_name("OUTPUT"),
+        .subcommand(
+            SubCommand::with_name("index")
+                .about("Walk a directory, hash its files and build a search index")
+                .arg(
+                    Arg::with_name(DIRECTORY)
+                        .help("Directory to walk")
+                        .index(1)
+                        .takes_value(true)
+                        .value_name("DIRECTORY")
+                        .default_value("."),
+                )
+                .arg(
+                    Arg::with_name(OUTPUT)
+                        .help("Output file path")
+                        .short("o")
+                        .long(OUTPUT)
+                        .takes_value(true)
+                        .value_name("OUTPUT"),
+                )
+                .arg(
+                    Arg::with_name(HASH)
+                        .help("Hash algorithm to digest file contents with")
+                        .long(HASH)
+                        .takes_value(true)
+                        .value_name("HASH")
+                        .possible_values(&["sha1", "sha256", "blake3"])
+                        .default_value("sha1"),
+                )
+                .arg(
+                    Arg::with_name(DUPLICATES)
+                        .help("Report files sharing an identical digest")
+                        .long(DUPLICATES),
+                )
+                .arg(
+                    Arg::with_name(CHUNKS)
+                        .help("Content-defined chunk each file and report a dedup ratio")
+                        .long(CHUNKS),
+                )
+                .arg(lang_arg())
+                .arg(index_dir_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("search")
+                .about("Run a one-shot query against the index")
+                .arg(
+                    Arg::with_name(QUERY)
+                        .help("Query")
+                        .index(1)
+                        .takes_value(true)
+                        .required(true)
+                        .value_name("QUERY"),
+                )
+                .args(&fuzzy_args())
+                .arg(lang_arg())
+                .arg(index_dir_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("serve")
+                .about("Serve the index over HTTP")
+                .arg(
+                    Arg::with_name(PORT)
+                        .help("Port to listen on")
+                        .short("p")
+                        .long(PORT)
+                        .takes_value(true)
+                        .value_name("PORT")
+                        .default_value(DEFAULT_PORT),
+                )
+                .args(&fuzzy_args())
+                .arg(lang_arg())
+                .arg(index_dir_arg()),
         )
 }
 
@@ -29,14 +184,55 @@ pub fn print_help() {
         .expect("Could not print command line help message");
 }
 
-pub fn handle_args() -> Option<CommandArg> {
+pub fn handle_args() -> Option<Command> {
     let matches = build_cli().get_matches();
 
-    let output = if let Some(o) = matches.value_of(OUTPUT) {
-        Some(PathBuf::from(o))
-    } else {
-        None
-    };
+    match matches.subcommand() {
+        ("index", Some(sub)) => {
+            let directory = PathBuf::from(sub.value_of(DIRECTORY)?);
+            let output = sub.value_of(OUTPUT).map(PathBuf::from);
+            let index_dir = PathBuf::from(sub.value_of(INDEX_DIR)?);
+            let algorithm = sub.value_of(HASH)?.parse().ok()?;
+            let duplicates = sub.is_present(DUPLICATES);
+            let chunks = sub.is_present(CHUNKS);
+            let lang = String::from(sub.value_of(LANG)?);
+
+            Some(Command::Index(IndexArgs {
+                directory,
+                output,
+                index_dir,
+                algorithm,
+                duplicates,
+                chunks,
+                lang,
+            }))
+        }
+        ("search", Some(sub)) => {
+            let query = String::from(sub.value_of(QUERY)?);
+            let index_dir = PathBuf::from(sub.value_of(INDEX_DIR)?);
+            let fuzzy = parse_fuzzy(sub);
+            let lang = String::from(sub.value_of(LANG)?);
+
+            Some(Command::Search(SearchArgs {
+                query,
+                index_dir,
+                fuzzy,
+                lang,
+            }))
+        }
+        ("serve", Some(sub)) => {
+            let port = sub.value_of(PORT)?.parse().ok()?;
+            let index_dir = PathBuf::from(sub.value_of(INDEX_DIR)?);
+            let fuzzy = parse_fuzzy(sub);
+            let lang = String::from(sub.value_of(LANG)?);
 
-    Some(CommandArg { output })
+            Some(Command::Serve(ServeArgs {
+                port,
+                index_dir,
+                fuzzy,
+                lang,
+            }))
+        }
+        _ => None,
+    }
 }
@@ -0,0 +1,255 @@
+use cli::IndexArgs;
+use processor::{self, ParsedFile};
+use progress::Progress;
+use schema;
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufWriter, StdoutLock, Write},
+    path::{Path, PathBuf},
+    sync::mpsc::{channel, Receiver, Sender},
+};
+use tantivy::{Document, Index};
+use threadpool::ThreadPool;
+
+enum Work {
+    Directory {
+        tx: Sender<Work>,
+        path: PathBuf,
+        index: usize,
+    },
+    Parsed {
+        entry: ParsedFile,
+        path: PathBuf,
+        index: usize,
+    },
+    Empty {
+        index: usize,
+    },
+    DiscoveryComplete {
+        count: u64,
+    },
+}
+
+enum Output<'a> {
+    File(BufWriter<File>),
+    Stdout {
+        stream: StdoutLock<'a>,
+        working_dir: PathBuf,
+    },
+}
+
+pub fn run(args: IndexArgs) {
+    let stdout = io::stdout();
+    let working_dir = args
+        .directory
+        .canonicalize()
+        .expect("Could not get working directory");
+    let algorithm = args.algorithm;
+    let pool = threadpool::Builder::new().build();
+    let mut pb = Progress::new(&pool);
+
+    let mut output = if let Some(out) = args.output {
+        pb.set_enabled(true);
+        Output::File(BufWriter::new(
+            File::create(&out).expect("Could not create output file"),
+        ))
+    } else {
+        Output::Stdout {
+            stream: stdout.lock(),
+            working_dir: working_dir.clone(),
+        }
+    };
+
+    let index_schema = schema::build(&args.lang);
+    let index = Index::create_in_dir(&args.index_dir, index_schema.schema.clone())
+        .expect("Could not create tantivy index");
+    schema::register_tokenizer(&index, &index_schema.tokenizer_name, &args.lang);
+    let mut index_writer = index
+        .writer(50_000_000)
+        .expect("Could not create index writer");
+
+    let title = index_schema.title;
+    let body = index_schema.body;
+
+    pb.build_status();
+
+    let rx = start_iter(working_dir, &pool);
+    let mut duplicates: HashMap<String, Vec<PathBuf>> = HashMap::new();
+    let mut chunk_store: HashMap<String, u64> = HashMap::new();
+    let mut total_chunk_bytes = 0u64;
+
+    while let Ok(result) = rx.recv() {
+        match result {
+            Work::Directory { tx, path, index } => {
+                let chunks = args.chunks;
+
+                pool.execute(move || {
+                    let entry = match processor::process(&path, algorithm, chunks) {
+                        Some(h) => h,
+                        None => {
+                            tx.send(Work::Empty { index })
+                                .expect("Could not signal pool");
+                            return;
+                        }
+                    };
+
+                    tx.send(Work::Parsed { path, entry, index })
+                        .expect("Could not signal pool");
+                });
+            }
+            Work::Empty { index: _ } => pb.inc(),
+            Work::Parsed {
+                entry,
+                path,
+                index: _,
+            } => {
+                print_hash(&mut output, &entry, &path);
+                let mut doc = Document::default();
+                doc.add_text(title, &format!("{}", path.display()));
+
+                if let Some(content) = entry.str_content() {
+                    doc.add_text(body, content);
+                }
+
+                index_writer.add_document(doc);
+
+                if args.duplicates {
+                    duplicates
+                        .entry(entry.hash().to_string())
+                        .or_insert_with(Vec::new)
+                        .push(path.clone());
+                }
+
+                if args.chunks {
+                    print_chunks(&path, &entry);
+
+                    for chunk in entry.chunks() {
+                        total_chunk_bytes += chunk.length;
+                        chunk_store
+                            .entry(chunk.digest.clone())
+                            .or_insert(chunk.length);
+                    }
+                }
+
+                pb.inc_success();
+            }
+            Work::DiscoveryComplete { count } => pb.build_bar(count),
+        }
+    }
+
+    index_writer.commit().expect("Could not commit tantivy");
+
+    if args.duplicates {
+        report_duplicates(&pb, &duplicates);
+    }
+
+    if args.chunks {
+        report_chunks(&pb, &chunk_store, total_chunk_bytes);
+    }
+}
+
+fn print_chunks(path: &Path, entry: &ParsedFile) {
+    println!("{}: {} chunks", path.display(), entry.chunks().len());
+
+    for chunk in entry.chunks() {
+        println!("  {}+{} {}", chunk.offset, chunk.length, chunk.digest);
+    }
+}
+
+fn report_chunks(pb: &Progress, chunk_store: &HashMap<String, u64>, total_chunk_bytes: u64) {
+    let unique_bytes: u64 = chunk_store.values().sum();
+
+    pb.report_dedup_ratio(total_chunk_bytes, unique_bytes);
+}
+
+fn report_duplicates(pb: &Progress, duplicates: &HashMap<String, Vec<PathBuf>>) {
+    let mut groups = 0;
+    let mut reclaimable_bytes = 0u64;
+
+    for paths in duplicates.values().filter(|paths| paths.len() > 1) {
+        groups += 1;
+
+        let size = paths
+            .first()
+            .and_then(|path| path.metadata().ok())
+            .map(|metadata| metadata.len())
+            .unwrap_or(0);
+
+        reclaimable_bytes += size * (paths.len() as u64 - 1);
+
+        println!("duplicate digest found in {} files:", paths.len());
+        for path in paths {
+            println!("  {}", path.display());
+        }
+    }
+
+    pb.report_duplicates(groups, reclaimable_bytes);
+}
+
+fn print_hash(output: &mut Output, entry: &ParsedFile, path: &Path) {
+    let content_type = entry
+        .content_type()
+        .map(|x| format!("{:?}", x))
+        .unwrap_or_else(|| String::from("EMPTY"));
+
+    match output {
+        Output::File(writer) => writeln!(
+            writer,
+            "{},{},{},{}",
+            entry.algorithm(),
+            entry.hash(),
+            content_type,
+            path.display()
+        ).expect("Could not write to file"),
+        Output::Stdout {
+            stream,
+            working_dir,
+        } => {
+            let absolute_path = path
+                .canonicalize()
+                .expect("Could not get absolute file path");
+            let diff = absolute_path
+                .strip_prefix(working_dir)
+                .expect("Could not generate relative path");
+
+            writeln!(
+                stream,
+                "{} {} {} {}",
+                entry.algorithm(),
+                entry.hash(),
+                content_type,
+                diff.display()
+            ).expect("Could not write to stdout");
+        }
+    }
+}
+
+fn start_iter(working_dir: PathBuf, pool: &ThreadPool) -> Receiver<Work> {
+    let (tx, rx) = channel();
+    let tx_send = tx.clone();
+
+    pool.execute(move || {
+        let iter = walkdir::WalkDir::new(working_dir)
+            .into_iter()
+            .filter_map(|x| x.ok());
+
+        let mut count = 0;
+
+        for (index, entry) in iter.enumerate() {
+            tx.send(Work::Directory {
+                tx: tx_send.clone(),
+                path: entry.path().into(),
+                index,
+            }).expect("Could not signal pool");
+
+            count = index;
+        }
+
+        tx.send(Work::DiscoveryComplete {
+            count: count as u64,
+        }).expect("Could not signal pool");
+    });
+
+    rx
+}
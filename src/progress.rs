@@ -85,4 +85,28 @@ impl<'a> Progress<'a> {
         self.inc();
         self.success += 1;
     }
+
+    /// Prints the final "N duplicate groups reclaiming M bytes" line once
+    /// the run is complete.
+    pub fn report_duplicates(&self, groups: usize, reclaimable_bytes: u64) {
+        eprintln!(
+            "{} duplicate groups reclaiming {} bytes",
+            groups, reclaimable_bytes
+        );
+    }
+
+    /// Prints the dedup ratio between bytes seen in chunks and bytes held
+    /// by distinct chunks once the run is complete.
+    pub fn report_dedup_ratio(&self, total_bytes: u64, unique_bytes: u64) {
+        let ratio = if unique_bytes == 0 {
+            0.0
+        } else {
+            total_bytes as f64 / unique_bytes as f64
+        };
+
+        eprintln!(
+            "{} bytes chunked, {} unique ({:.2}x dedup ratio)",
+            total_bytes, unique_bytes, ratio
+        );
+    }
 }
@@ -0,0 +1,132 @@
+use tantivy::query::{BooleanQuery, FuzzyTermQuery, Occur, Query, QueryParser};
+use tantivy::schema::Field;
+use tantivy::tokenizer::{TextAnalyzer, TokenStream};
+use tantivy::{Index, Term};
+
+/// Typo-tolerant matching for a query, either turned on for every term via
+/// `--fuzzy <distance>` or per-term via a `~N` suffix (e.g. `let mutt~2`).
+#[derive(Debug, Clone, Copy)]
+pub struct FuzzyOptions {
+    pub distance: u8,
+    /// Count a transposed pair of adjacent characters (`ab` -> `ba`) as a
+    /// single edit instead of two, per tantivy's `FuzzyTermQuery`.
+    pub transpositions: bool,
+}
+
+/// Builds the query tantivy should run for `text`, using the existing
+/// `QueryParser` across `fields` unless fuzzy matching is requested, in
+/// which case each term is matched against a Levenshtein automaton instead
+/// of requiring an exact token.
+///
+/// `tokenizer_name` must be the analyzer registered for `fields` (see
+/// `schema::register_tokenizer`) so fuzzy terms are lowercased, folded and
+/// stemmed the same way the indexed tokens were — otherwise a fuzzy term
+/// compares against text the index never stored.
+pub fn parse(
+    index: &Index,
+    fields: &[Field],
+    tokenizer_name: &str,
+    text: &str,
+    fuzzy: Option<FuzzyOptions>,
+) -> Result<Box<dyn Query>, String> {
+    let terms: Vec<(String, Option<u8>)> =
+        text.split_whitespace().map(split_fuzzy_suffix).collect();
+
+    let is_fuzzy = fuzzy.is_some() || terms.iter().any(|(_, distance)| distance.is_some());
+
+    if !is_fuzzy {
+        let query_parser = QueryParser::for_index(index, fields.to_vec());
+        return query_parser
+            .parse_query(text)
+            .map_err(|err| format!("{:?}", err));
+    }
+
+    let default_distance = fuzzy.map(|f| f.distance).unwrap_or(1);
+    let transpositions = fuzzy.map(|f| f.transpositions).unwrap_or(false);
+    let analyzer = index
+        .tokenizers()
+        .get(tokenizer_name)
+        .ok_or_else(|| format!("unknown tokenizer `{}`", tokenizer_name))?;
+
+    let clauses: Vec<(Occur, Box<dyn Query>)> = terms
+        .into_iter()
+        .flat_map(|(word, distance)| {
+            let distance = distance.unwrap_or(default_distance);
+            let tokens = normalize(&analyzer, &word);
+
+            tokens
+                .into_iter()
+                .flat_map(move |token| {
+                    fields.iter().map(move |field| {
+                        let term = Term::from_field_text(*field, &token);
+                        let fuzzy_query: Box<dyn Query> =
+                            Box::new(FuzzyTermQuery::new(term, distance, transpositions));
+
+                        (Occur::Should, fuzzy_query)
+                    })
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+    Ok(Box::new(BooleanQuery::from(clauses)))
+}
+
+/// Runs `word` through the registered analyzer so fuzzy terms line up with
+/// the lowercased/folded/stemmed tokens the index actually stored.
+fn normalize(analyzer: &TextAnalyzer, word: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut token_stream = analyzer.token_stream(word);
+
+    token_stream.process(&mut |token| tokens.push(token.text.clone()));
+
+    tokens
+}
+
+/// Splits a `word~N` suffix off a query term, e.g. `mutt~2` becomes
+/// (`"mutt"`, `Some(2)`). Terms without a valid suffix are left untouched.
+fn split_fuzzy_suffix(word: &str) -> (String, Option<u8>) {
+    if let Some(pos) = word.rfind('~') {
+        if let Ok(distance) = word[pos + 1..].parse::<u8>() {
+            return (word[..pos].to_string(), Some(distance));
+        }
+    }
+
+    (word.to_string(), None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_a_valid_suffix() {
+        assert_eq!(
+            split_fuzzy_suffix("mutt~2"),
+            ("mutt".to_string(), Some(2))
+        );
+    }
+
+    #[test]
+    fn leaves_a_word_without_a_tilde_untouched() {
+        assert_eq!(split_fuzzy_suffix("mutt"), ("mutt".to_string(), None));
+    }
+
+    #[test]
+    fn treats_a_trailing_tilde_with_no_digits_as_plain_text() {
+        assert_eq!(split_fuzzy_suffix("mutt~"), ("mutt~".to_string(), None));
+    }
+
+    #[test]
+    fn treats_a_non_numeric_suffix_as_plain_text() {
+        assert_eq!(split_fuzzy_suffix("mutt~abc"), ("mutt~abc".to_string(), None));
+    }
+
+    #[test]
+    fn splits_on_the_last_tilde() {
+        assert_eq!(
+            split_fuzzy_suffix("a~b~2"),
+            ("a~b".to_string(), Some(2))
+        );
+    }
+}
@@ -0,0 +1,77 @@
+use tantivy::schema::{
+    Field, IndexRecordOption, Schema, SchemaBuilder, TextFieldIndexing, TextOptions,
+};
+use tantivy::tokenizer::{
+    AsciiFoldingFilter, Language, LowerCaser, SimpleTokenizer, Stemmer, TextAnalyzer,
+};
+use tantivy::Index;
+
+/// Default on-disk location for the tantivy index, used when `--index-dir`
+/// is not given.
+pub const DEFAULT_INDEX_DIR: &str = "/temp/tantivy";
+
+/// Default language for the analyzer chain, used when `--lang` is not
+/// given.
+pub const DEFAULT_LANG: &str = "en";
+
+/// The fields `hashy` indexes every file under, shared by the `index`,
+/// `search` and `serve` subcommands so they can never drift apart.
+pub struct IndexSchema {
+    pub schema: Schema,
+    pub title: Field,
+    pub body: Field,
+    pub tokenizer_name: String,
+}
+
+pub fn build(lang: &str) -> IndexSchema {
+    let tokenizer_name = format!("hashy_{}", lang);
+
+    let text_indexing = TextFieldIndexing::default()
+        .set_tokenizer(&tokenizer_name)
+        .set_index_option(IndexRecordOption::WithFreqsAndPositions);
+
+    // Stored so a `SnippetGenerator` can pull the matching window of text
+    // back out of a hit instead of only knowing that it matched.
+    let text_options = TextOptions::default()
+        .set_indexing_options(text_indexing)
+        .set_stored();
+
+    let mut schema_builder = SchemaBuilder::default();
+    let title = schema_builder.add_text_field("title", text_options.clone());
+    let body = schema_builder.add_text_field("body", text_options);
+
+    IndexSchema {
+        schema: schema_builder.build(),
+        title,
+        body,
+        tokenizer_name,
+    }
+}
+
+/// Registers the analyzer chain a schema's fields were built to use —
+/// accent folding, lowercasing, then a Snowball-style stemmer for `lang` —
+/// on `index`. Must be called before indexing or querying so the two sides
+/// can't drift apart: an indexer and searcher disagreeing on tokenization
+/// just silently stops matching anything.
+pub fn register_tokenizer(index: &Index, tokenizer_name: &str, lang: &str) {
+    let analyzer = TextAnalyzer::from(SimpleTokenizer)
+        .filter(AsciiFoldingFilter)
+        .filter(LowerCaser)
+        .filter(Stemmer::new(language_for(lang)));
+
+    index.tokenizers().register(tokenizer_name, analyzer);
+}
+
+fn language_for(lang: &str) -> Language {
+    match lang {
+        "en" => Language::English,
+        "fr" => Language::French,
+        "de" => Language::German,
+        "es" => Language::Spanish,
+        "it" => Language::Italian,
+        "pt" => Language::Portuguese,
+        "nl" => Language::Dutch,
+        "ru" => Language::Russian,
+        _ => Language::English,
+    }
+}
@@ -0,0 +1,39 @@
+use cli::SearchArgs;
+use query;
+use schema;
+use snippet;
+use tantivy::{collector::TopCollector, Index};
+
+pub fn run(args: SearchArgs) {
+    let index = Index::open_in_dir(&args.index_dir).expect("Could not open index");
+    let index_schema = schema::build(&args.lang);
+    schema::register_tokenizer(&index, &index_schema.tokenizer_name, &args.lang);
+
+    let fields = [index_schema.title, index_schema.body];
+
+    let searcher = index.searcher();
+    let parsed_query = query::parse(
+        &index,
+        &fields,
+        &index_schema.tokenizer_name,
+        &args.query,
+        args.fuzzy,
+    )
+    .expect("Could not parse query");
+    let mut top_collector = TopCollector::with_limit(10);
+
+    searcher
+        .search(&*parsed_query, &mut top_collector)
+        .expect("Could not search");
+
+    for doc_address in top_collector.docs() {
+        let doc = searcher.doc(&doc_address).expect("Could not retrieve doc");
+        let path = doc
+            .get_first(index_schema.title)
+            .and_then(|value| value.text())
+            .unwrap_or("<unknown path>");
+        let snippet = snippet::highlight(&searcher, &*parsed_query, index_schema.body, &doc);
+
+        println!("{}: {}", path, snippet);
+    }
+}
@@ -0,0 +1,237 @@
+use cli::ServeArgs;
+use query::{self, FuzzyOptions};
+use schema;
+use snippet;
+use std::io::Cursor;
+use tantivy::{collector::TopCollector, schema::Field, schema::Schema, Index, Searcher};
+use tiny_http::{Header, Response, Server};
+
+const DEFAULT_LIMIT: usize = 10;
+
+/// `TopCollector::with_limit` panics below 1 and an unbounded `limit` lets
+/// a client force an arbitrarily large collector allocation, so
+/// client-supplied limits are clamped to this range.
+const MIN_LIMIT: usize = 1;
+const MAX_LIMIT: usize = 1000;
+
+pub fn run(args: ServeArgs) {
+    let index = Index::open_in_dir(&args.index_dir).expect("Could not open index");
+    let index_schema = schema::build(&args.lang);
+    schema::register_tokenizer(&index, &index_schema.tokenizer_name, &args.lang);
+
+    let fields = [index_schema.title, index_schema.body];
+
+    let searcher = index.searcher();
+
+    let server = Server::http(("0.0.0.0", args.port)).expect("Could not bind to port");
+
+    println!("Listening on port {}", args.port);
+
+    for request in server.incoming_requests() {
+        let (query, limit) = parse_query_string(request.url());
+
+        let response = match query {
+            Some(q) => run_search(
+                &index,
+                &fields,
+                &index_schema.tokenizer_name,
+                index_schema.body,
+                &searcher,
+                &index_schema.schema,
+                &q,
+                limit,
+                args.fuzzy,
+            ),
+            None => bad_request("missing `q` parameter"),
+        };
+
+        if let Err(err) = request.respond(response) {
+            eprintln!("Could not write response: {}", err);
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_search(
+    index: &Index,
+    fields: &[Field],
+    tokenizer_name: &str,
+    body: Field,
+    searcher: &Searcher,
+    schema: &Schema,
+    query: &str,
+    limit: usize,
+    fuzzy: Option<FuzzyOptions>,
+) -> Response<Cursor<Vec<u8>>> {
+    let parsed_query = match query::parse(index, fields, tokenizer_name, query, fuzzy) {
+        Ok(q) => q,
+        Err(_) => return bad_request("could not parse query"),
+    };
+
+    let mut top_collector = TopCollector::with_limit(limit);
+
+    if searcher.search(&*parsed_query, &mut top_collector).is_err() {
+        return bad_request("could not execute search");
+    }
+
+    let hits: Vec<String> = top_collector
+        .docs()
+        .iter()
+        .filter_map(|addr| searcher.doc(addr).ok())
+        .map(|doc| {
+            let highlight = snippet::highlight(searcher, &*parsed_query, body, &doc);
+
+            format!(
+                "{{\"doc\":{},\"snippet\":{}}}",
+                schema.to_json(&doc),
+                json_string(&highlight)
+            )
+        })
+        .collect();
+
+    Response::from_string(format!("[{}]", hits.join(","))).with_header(json_content_type())
+}
+
+fn json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped.push('"');
+    escaped
+}
+
+fn bad_request(message: &str) -> Response<Cursor<Vec<u8>>> {
+    Response::from_string(format!("{{\"error\":\"{}\"}}", message))
+        .with_status_code(400)
+        .with_header(json_content_type())
+}
+
+fn json_content_type() -> Header {
+    Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+        .expect("Could not build header")
+}
+
+/// Pulls `q` and `limit` out of a request path like `/search?q=foo&limit=5`.
+fn parse_query_string(url: &str) -> (Option<String>, usize) {
+    let query_string = match url.splitn(2, '?').nth(1) {
+        Some(q) => q,
+        None => return (None, DEFAULT_LIMIT),
+    };
+
+    let mut query = None;
+    let mut limit = DEFAULT_LIMIT;
+
+    for pair in query_string.split('&') {
+        let mut parts = pair.splitn(2, '=');
+        let key = parts.next().unwrap_or("");
+        let value = parts.next().unwrap_or("");
+
+        match key {
+            "q" => query = Some(decode_query_value(value)),
+            "limit" => {
+                limit = value
+                    .parse()
+                    .unwrap_or(DEFAULT_LIMIT)
+                    .max(MIN_LIMIT)
+                    .min(MAX_LIMIT)
+            }
+            _ => {}
+        }
+    }
+
+    (query, limit)
+}
+
+/// Percent-decodes `value` byte-by-byte rather than char-by-char, so a
+/// decoded `%XX` is treated as a raw UTF-8 byte instead of a Latin-1 code
+/// point — multibyte query terms only round-trip if the bytes of a single
+/// UTF-8 sequence survive together.
+fn decode_query_value(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => {
+                decoded.push(b' ');
+                i += 1;
+            }
+            b'%' if i + 3 <= bytes.len() => {
+                let hi = hex_digit(bytes[i + 1]);
+                let lo = hex_digit(bytes[i + 2]);
+
+                match (hi, lo) {
+                    (Some(hi), Some(lo)) => {
+                        decoded.push((hi << 4) | lo);
+                        i += 3;
+                    }
+                    _ => {
+                        decoded.push(b'%');
+                        i += 1;
+                    }
+                }
+            }
+            byte => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn hex_digit(byte: u8) -> Option<u8> {
+    (byte as char).to_digit(16).map(|d| d as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_string_escapes_control_chars() {
+        assert_eq!(
+            json_string("line1\nline2\ttab\"quote\\back\u{0001}"),
+            "\"line1\\nline2\\ttab\\\"quote\\\\back\\u0001\""
+        );
+    }
+
+    #[test]
+    fn json_string_leaves_plain_text_alone() {
+        assert_eq!(json_string("hello world"), "\"hello world\"");
+    }
+
+    #[test]
+    fn decode_query_value_turns_plus_into_space() {
+        assert_eq!(decode_query_value("let+mut"), "let mut");
+    }
+
+    #[test]
+    fn decode_query_value_handles_a_percent_at_end_of_string() {
+        assert_eq!(decode_query_value("abc%"), "abc%");
+    }
+
+    #[test]
+    fn decode_query_value_decodes_a_multibyte_utf8_sequence() {
+        assert_eq!(decode_query_value("%C3%A9"), "é");
+    }
+
+    #[test]
+    fn decode_query_value_does_not_panic_on_invalid_utf8() {
+        assert_eq!(decode_query_value("%C3%ZZ"), "\u{FFFD}%ZZ");
+    }
+}
@@ -0,0 +1,42 @@
+use tantivy::query::Query;
+use tantivy::schema::Field;
+use tantivy::{Document, Searcher, SnippetGenerator};
+
+/// Roughly how much matching context to show per hit.
+const MAX_SNIPPET_CHARS: usize = 150;
+
+/// Builds a short window of `doc`'s body around the terms that matched
+/// `query`, marking the matches with `**term**` the way the rest of this
+/// CLI favours plain text over markup.
+pub fn highlight(searcher: &Searcher, query: &dyn Query, body: Field, doc: &Document) -> String {
+    let mut generator = match SnippetGenerator::create(searcher, query, body) {
+        Ok(generator) => generator,
+        Err(_) => return String::new(),
+    };
+
+    generator.set_max_num_chars(MAX_SNIPPET_CHARS);
+
+    to_markdown(&generator.snippet_from_doc(doc))
+}
+
+/// Marks `snippet`'s highlighted ranges with `**term**` directly from its
+/// raw fragment text, rather than going through `Snippet::to_html()` and
+/// stripping tags back out — that route leaves the fragment HTML-escaped
+/// (`<`, `>`, `&`) with no markup left to strip it back with, since this
+/// CLI wants plain text, not HTML.
+fn to_markdown(snippet: &tantivy::Snippet) -> String {
+    let fragment = snippet.fragments();
+    let mut marked = String::with_capacity(fragment.len());
+    let mut cursor = 0;
+
+    for range in snippet.highlighted() {
+        marked.push_str(&fragment[cursor..range.start]);
+        marked.push_str("**");
+        marked.push_str(&fragment[range.start..range.end]);
+        marked.push_str("**");
+        cursor = range.end;
+    }
+
+    marked.push_str(&fragment[cursor..]);
+    marked
+}